@@ -0,0 +1,71 @@
+use clap::Parser;
+use serde::Deserialize;
+
+#[derive(Parser, Debug)]
+#[clap(name = "billing-notifier", about = "Posts AWS cost summaries to Slack")]
+pub struct Cli {
+    /// Path to the TOML config file describing accounts and delivery settings.
+    #[clap(short, long, default_value = "config.toml")]
+    pub config: String,
+
+    /// Overrides the config file's granularity (daily|monthly|hourly).
+    #[clap(short, long)]
+    pub granularity: Option<String>,
+
+    /// Print the report instead of delivering it.
+    #[clap(long)]
+    pub dry_run: bool,
+
+    /// Stay resident and report on the cadence configured under `[schedule]`,
+    /// instead of running once and exiting.
+    #[clap(long)]
+    pub watch: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Config {
+    pub slack_token: Option<String>,
+    pub channel_id: Option<String>,
+    pub granularity: Option<String>,
+    pub schedule: Option<ScheduleConfig>,
+    #[serde(default)]
+    pub accounts: Vec<AccountConfig>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AccountConfig {
+    pub name: String,
+    pub account_id: String,
+    pub budget_name: String,
+    /// Named profile (from `~/.aws/credentials`) to assume when querying this
+    /// account. Falls back to the default profile (`AWS_PROFILE`, else
+    /// `default`) when unset; environment or instance-metadata credentials
+    /// are not used.
+    pub aws_profile: Option<String>,
+    /// Fraction of the budget limit that must be crossed before a notification
+    /// is sent, e.g. 0.8 for 80%. Defaults to 80%.
+    #[serde(default = "default_budget_threshold_percentage")]
+    pub budget_threshold_percentage: f32,
+}
+
+fn default_budget_threshold_percentage() -> f32 {
+    0.8
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ScheduleConfig {
+    /// "daily" or "weekly".
+    pub frequency: String,
+    /// UTC hour (0-23) to run at.
+    pub hour: u32,
+    /// Required when `frequency` is "weekly", e.g. "monday".
+    pub day: Option<String>,
+}
+
+impl Config {
+    pub fn load(path: &str) -> Result<Config, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|_| format!("Could not read config file: {}", path))?;
+        toml::from_str(&contents).map_err(|_| "Could not parse config file".to_owned())
+    }
+}
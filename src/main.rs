@@ -1,16 +1,27 @@
+mod config;
+
 use std::env;
+use async_trait::async_trait;
+use clap::Parser;
 use slack_api::chat::{PostMessageRequest, PostMessageResponse};
-use rusoto_ce::{CostExplorerClient, CostExplorer, GetCostAndUsageRequest, DateInterval, MetricValue, ResultByTime};
-use rusoto_core::{Client, HttpClient, Region};
-use reqwest::header::DATE;
-use chrono::{Date, Utc, Duration};
+use rusoto_ce::{CostExplorerClient, CostExplorer, GetCostAndUsageRequest, GroupDefinition, DateInterval};
+use rusoto_budgets::{Budgets, BudgetsClient, DescribeBudgetRequest};
+use rusoto_core::{HttpClient, Region};
+use rusoto_credential::ProfileProvider;
+use chrono::{Date, DateTime, Datelike, Duration, TimeZone, Utc, Weekday};
 use std::ops::Sub;
 use std::str::FromStr;
 
+use config::{AccountConfig, Cli, Config, ScheduleConfig};
 
-const DAILY: &str = "DAILY";
 const UNBLENDED_COST: &str = "UnblendedCost";
+const SERVICE_DIMENSION: &str = "SERVICE";
+const DEFAULT_TOP_N_SERVICES: usize = 5;
+const DEFAULT_ANOMALY_TRAILING_DAYS: i64 = 7;
+const DEFAULT_ANOMALY_STD_DEV_MULTIPLIER: f32 = 2.0;
+const DEFAULT_ANOMALY_PERCENT_THRESHOLD: f32 = 0.5;
 
+#[derive(Clone, Copy)]
 enum CostGranularityType {
     Monthly,
     Daily,
@@ -27,61 +38,390 @@ impl CostGranularityType {
     }
 }
 
+impl FromStr for CostGranularityType {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "monthly" => Ok(CostGranularityType::Monthly),
+            "daily" => Ok(CostGranularityType::Daily),
+            "hourly" => Ok(CostGranularityType::Hourly),
+            other => Err(format!("Unknown granularity: {}", other)),
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let token = env::var("SLACK_API_TOKEN")
-        .map_err(|_| "SLACK_API_TOKEN env var must be set")?;
-    let channel_id = env::var("SLACK_CHANNEL_ID")
-        .map_err(|_| "SLACK_CHANNEL_ID env var must be set")?;
-
-    let aws_cost_client = AwsCostClient::default();
-    let cost = aws_cost_client
-        .get_cost(CostGranularityType::Monthly)
+    let cli = Cli::parse();
+    let config = Config::load(cli.config.as_str())?;
+
+    let granularity_name = cli
+        .granularity
+        .clone()
+        .or_else(|| config.granularity.clone())
+        .unwrap_or_else(|| "monthly".to_owned());
+    let granularity = CostGranularityType::from_str(granularity_name.as_str())?;
+
+    if cli.watch {
+        run_scheduler(&cli, &config, granularity).await?;
+    } else {
+        report_all_accounts(&cli, &config, granularity).await;
+    }
+
+    Ok(())
+}
+
+async fn report_all_accounts(cli: &Cli, config: &Config, granularity: CostGranularityType) {
+    for account in &config.accounts {
+        let result = match build_credentials_provider(account).and_then(AwsCostClient::new) {
+            Ok(aws_cost_client) => report_account_cost(cli, config, account, granularity, &aws_cost_client, &aws_cost_client).await,
+            Err(error) => Err(error),
+        };
+        if let Err(error) = result {
+            println!("[{}] Failed to report cost: {}", account.name, error);
+        }
+    }
+}
+
+/// Stays resident, sleeping until the next scheduled run computed from
+/// `config.schedule`, then reports every account and repeats.
+async fn run_scheduler(cli: &Cli, config: &Config, granularity: CostGranularityType) -> Result<(), String> {
+    let schedule = config
+        .schedule
+        .as_ref()
+        .ok_or("schedule must be set in the config file to use --watch".to_owned())?;
+
+    loop {
+        let now = Utc::now();
+        let next_run = next_run_instant(schedule, now)?;
+        let sleep_duration = (next_run - now).to_std().unwrap_or(std::time::Duration::from_secs(0));
+        println!(
+            "Next run at {} UTC, sleeping for {:?}",
+            next_run.format("%Y-%m-%d %H:%M UTC"),
+            sleep_duration,
+        );
+        tokio::time::sleep(sleep_duration).await;
+
+        report_all_accounts(cli, config, granularity).await;
+    }
+}
+
+/// Computes the next instant matching `schedule`, strictly after `now`.
+fn next_run_instant(schedule: &ScheduleConfig, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    if schedule.hour > 23 {
+        return Err(format!("schedule.hour must be between 0 and 23, got {}", schedule.hour));
+    }
+
+    let mut candidate = Utc.ymd(now.year(), now.month(), now.day()).and_hms(schedule.hour, 0, 0);
+
+    match schedule.frequency.to_lowercase().as_str() {
+        "daily" => {
+            while candidate <= now {
+                candidate = candidate + Duration::days(1);
+            }
+        }
+        "weekly" => {
+            let day = schedule
+                .day
+                .as_deref()
+                .ok_or("schedule.day must be set when frequency is \"weekly\"".to_owned())?;
+            let target_weekday = Weekday::from_str(day).map_err(|_| format!("Unknown weekday: {}", day))?;
+            while candidate.weekday() != target_weekday || candidate <= now {
+                candidate = candidate + Duration::days(1);
+            }
+        }
+        other => return Err(format!("Unknown schedule frequency: {}", other)),
+    }
+
+    Ok(candidate)
+}
+
+/// Builds the credentials provider used to query `account`'s AWS account.
+/// Uses the named profile from `account.aws_profile` when set, falling back
+/// to the default profile (`AWS_PROFILE`, else `default`) otherwise. Only the
+/// shared credentials profile is read; credentials from environment variables
+/// or instance metadata are not used.
+fn build_credentials_provider(account: &AccountConfig) -> Result<ProfileProvider, String> {
+    let mut provider = ProfileProvider::new().map_err(|error| error.to_string())?;
+    if let Some(profile) = account.aws_profile.as_ref() {
+        provider.set_profile(profile);
+    }
+    Ok(provider)
+}
+
+/// TLS-enabled HTTP client shared by `AwsCostClient` and `AwsBudgetClient`,
+/// both of which talk to the single `us-east-1` endpoint for their API.
+fn new_us_east_1_http_client() -> Result<HttpClient, String> {
+    HttpClient::new().map_err(|error| error.to_string())
+}
+
+async fn report_account_cost(
+    cli: &Cli,
+    config: &Config,
+    account: &AccountConfig,
+    granularity: CostGranularityType,
+    cost_provider: &dyn CostProvider,
+    aws_cost_client: &AwsCostClient,
+) -> Result<(), String> {
+    let cost = cost_provider.get_cost(granularity).await.map_err(|error| error.to_string())?;
+
+    let credentials_provider = build_credentials_provider(account)?;
+    let aws_budget_client = AwsBudgetClient::new(credentials_provider, account.account_id.as_str())?;
+    let budget = aws_budget_client
+        .get_budget(account.budget_name.as_str())
         .await?;
 
-    let message = format!("Usage cost: {:?} {}", cost.amount, cost.unit);
-    let client = SlackClient::new(
-        token.as_str(),
-        channel_id.as_str(),
-    )?;
-    let response = client
-        .send_message(message.as_str())
+    if !cost.exceeds_threshold(budget.limit, account.budget_threshold_percentage) {
+        println!(
+            "[{}] Spend {:?} {} has not crossed {:.0}% of budget limit {:?} {}, skipping notification",
+            account.name,
+            cost.amount,
+            cost.unit,
+            account.budget_threshold_percentage * 100.0,
+            budget.limit,
+            budget.unit,
+        );
+        return Ok(());
+    }
+
+    let service_costs = aws_cost_client.get_cost_by_service(granularity).await?;
+
+    let daily_amounts = aws_cost_client
+        .get_daily_costs(DEFAULT_ANOMALY_TRAILING_DAYS)
         .await?;
-    println!("Message sent successfully {:?}", response);
+    let anomaly = detect_anomaly(
+        &daily_amounts,
+        DEFAULT_ANOMALY_STD_DEV_MULTIPLIER,
+        DEFAULT_ANOMALY_PERCENT_THRESHOLD,
+    );
+
+    let message = format_report_message(account.name.as_str(), &cost, &budget, &service_costs, anomaly.as_ref());
+
+    let notifier = build_notifier(cli.dry_run, config)?;
+    notifier.deliver(Utc::now(), message.as_str()).await?;
+    println!("[{}] Message sent successfully", account.name);
     Ok(())
 }
 
-#[derive(Debug)]
+/// Selects the delivery backend. `--dry-run` always prints instead of sending;
+/// otherwise `NOTIFIER_BACKEND` (defaults to `slack`) picks between Slack, using
+/// the token/channel from the config file, and a generic webhook.
+fn build_notifier(dry_run: bool, config: &Config) -> Result<Box<dyn Notifier>, String> {
+    if dry_run {
+        return Ok(Box::new(DryRunNotifier::new()));
+    }
+
+    let backend = env::var("NOTIFIER_BACKEND").unwrap_or_else(|_| "slack".to_owned());
+    match backend.as_str() {
+        "webhook" => {
+            let webhook_url = env::var("WEBHOOK_URL")
+                .map_err(|_| "WEBHOOK_URL env var must be set".to_owned())?;
+            Ok(Box::new(WebhookNotifier::new(webhook_url.as_str())))
+        }
+        "slack" => {
+            let token = config
+                .slack_token
+                .as_ref()
+                .ok_or("slack_token must be set in the config file".to_owned())?;
+            let channel_id = config
+                .channel_id
+                .as_ref()
+                .ok_or("channel_id must be set in the config file".to_owned())?;
+            Ok(Box::new(SlackClient::new(token.as_str(), channel_id.as_str())?))
+        }
+        other => Err(format!("Unknown NOTIFIER_BACKEND: {}", other)),
+    }
+}
+
+/// A delivery backend for cost reports. `when` is the report's reference
+/// timestamp so every backend can format it consistently.
+#[async_trait]
+trait Notifier {
+    async fn deliver(&self, when: DateTime<Utc>, message: &str) -> Result<(), String>;
+}
+
+struct DryRunNotifier;
+
+impl DryRunNotifier {
+    fn new() -> Self {
+        DryRunNotifier
+    }
+}
+
+#[async_trait]
+impl Notifier for DryRunNotifier {
+    async fn deliver(&self, when: DateTime<Utc>, message: &str) -> Result<(), String> {
+        println!("[dry-run] [{}] {}", when.format("%Y-%m-%d %H:%M UTC"), message);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
 struct Cost {
     amount: f32,
     unit: String,
 }
 
-fn get_date_interval_from_end_date(end_date: Date<Utc>) -> DateInterval {
-    let before_one_day = end_date.sub(Duration::days(1));
+impl Cost {
+    fn exceeds_threshold(&self, budget_limit: f32, percentage: f32) -> bool {
+        self.amount >= budget_limit * percentage
+    }
+}
+
+#[derive(Debug)]
+struct ServiceCost {
+    name: String,
+    amount: f32,
+    unit: String,
+}
+
+/// Renders the first `top_n` entries of `service_costs` as a numbered,
+/// newline-separated list. The entries are not sorted here; callers pass them
+/// already ordered by descending spend (as `get_cost_by_service` returns them).
+fn format_top_services(service_costs: &[ServiceCost], top_n: usize) -> String {
+    service_costs
+        .iter()
+        .take(top_n)
+        .enumerate()
+        .map(|(index, service_cost)| format!(
+            "{}. {}: {:?} {}",
+            index + 1,
+            service_cost.name,
+            service_cost.amount,
+            service_cost.unit,
+        ))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[derive(Debug)]
+struct Budget {
+    limit: f32,
+    unit: String,
+}
+
+/// Renders the report delivered to Slack/webhook: headline spend vs. budget,
+/// the top services by spend, and an anomaly warning when `anomaly` is
+/// `Some` and flagged as anomalous. Pure and AWS-free so it can be unit
+/// tested without live clients.
+fn format_report_message(
+    account_name: &str,
+    cost: &Cost,
+    budget: &Budget,
+    service_costs: &[ServiceCost],
+    anomaly: Option<&Anomaly>,
+) -> String {
+    let mut message = format!(
+        "[{}] Usage cost: {:?} {} ({:.0}% of budget limit {:?} {})\n{}",
+        account_name,
+        cost.amount,
+        cost.unit,
+        (cost.amount / budget.limit) * 100.0,
+        budget.limit,
+        budget.unit,
+        format_top_services(service_costs, DEFAULT_TOP_N_SERVICES),
+    );
+    if let Some(anomaly) = anomaly {
+        if anomaly.is_anomalous {
+            message.push_str(format!(
+                "\n:warning: Daily spend anomaly detected: {:?} vs mean {:.2} (std dev {:.2}), {:.0}% change from previous day",
+                anomaly.latest_amount,
+                anomaly.mean,
+                anomaly.std_dev,
+                anomaly.percent_change * 100.0,
+            ).as_str());
+        }
+    }
+    message
+}
+
+fn get_date_interval_from_end_date(end_date: Date<Utc>, start_offset_days: i64) -> DateInterval {
+    let start_date = end_date.sub(Duration::days(start_offset_days));
     DateInterval {
-        start: before_one_day.format("%Y-%m-%d").to_string(),
+        start: start_date.format("%Y-%m-%d").to_string(),
         end: end_date.format("%Y-%m-%d").to_string(),
     }
 }
 
+#[derive(Debug)]
+struct Anomaly {
+    latest_amount: f32,
+    mean: f32,
+    std_dev: f32,
+    percent_change: f32,
+    is_anomalous: bool,
+}
+
+/// Flags the latest day in `daily_amounts` (ordered oldest to newest) as anomalous
+/// when it exceeds `mean + std_dev_multiplier * std_dev` over the trailing history,
+/// or when the day-over-day percent change exceeds `percent_threshold`. Falls back
+/// to the percentage check alone when the trailing history has zero variance, and
+/// returns `None` when there isn't enough history yet (e.g. the first run).
+fn detect_anomaly(daily_amounts: &[f32], std_dev_multiplier: f32, percent_threshold: f32) -> Option<Anomaly> {
+    if daily_amounts.len() < 2 {
+        return None;
+    }
+
+    let (history, latest) = daily_amounts.split_at(daily_amounts.len() - 1);
+    let latest_amount = latest[0];
+    let prior_amount = *history.last().unwrap();
+
+    let mean = history.iter().sum::<f32>() / history.len() as f32;
+    let std_dev = if history.len() > 1 {
+        let variance = history.iter().map(|amount| (amount - mean).powi(2)).sum::<f32>() / (history.len() - 1) as f32;
+        variance.sqrt()
+    } else {
+        0.0
+    };
+
+    let percent_change = if prior_amount != 0.0 {
+        (latest_amount - prior_amount) / prior_amount
+    } else {
+        0.0
+    };
+
+    let is_anomalous = if std_dev > 0.0 {
+        latest_amount > mean + std_dev_multiplier * std_dev || percent_change.abs() > percent_threshold
+    } else {
+        percent_change.abs() > percent_threshold
+    };
+
+    Some(Anomaly {
+        latest_amount,
+        mean,
+        std_dev,
+        percent_change,
+        is_anomalous,
+    })
+}
+
 struct AwsCostClient {
     client: CostExplorerClient,
 }
 
 impl AwsCostClient {
-    fn default() -> AwsCostClient {
-        AwsCostClient {
-            client: CostExplorerClient::new(Region::UsEast1)
-        }
+    /// Cost Explorer is a single-endpoint API served only out of `us-east-1`,
+    /// regardless of which region the queried account's resources live in, so
+    /// the region is hardcoded rather than taken from account config.
+    fn new(credentials_provider: ProfileProvider) -> Result<AwsCostClient, String> {
+        let http_client = new_us_east_1_http_client()?;
+        Ok(AwsCostClient {
+            client: CostExplorerClient::new_with(http_client, credentials_provider, Region::UsEast1),
+        })
     }
-    async fn get_cost(&self, cost_granularity_type: CostGranularityType) -> Result<Cost, String> {
+
+    async fn get_cost_by_service(&self, cost_granularity_type: CostGranularityType) -> Result<Vec<ServiceCost>, String> {
         let response = self.client
             .get_cost_and_usage(
                 GetCostAndUsageRequest {
-                    granularity: Some(cost_granularity_type.name()),
-                    time_period: get_date_interval_from_end_date(Utc::today()),
-                    metrics: Some(vec!(UNBLENDED_COST.to_owned())),
+                    granularity: cost_granularity_type.name(),
+                    time_period: get_date_interval_from_end_date(Utc::today(), 1),
+                    metrics: vec!(UNBLENDED_COST.to_owned()),
+                    group_by: Some(vec!(GroupDefinition {
+                        key: Some(SERVICE_DIMENSION.to_owned()),
+                        type_: Some("DIMENSION".to_owned()),
+                    })),
                     ..Default::default()
                 })
             .await
@@ -93,23 +433,132 @@ impl AwsCostClient {
         let first_result = results_by_times
             .first()
             .ok_or("Nothing first result".to_owned())?;
+        let groups = first_result
+            .groups
+            .as_ref()
+            .ok_or("Nothing groups".to_owned())?;
+
+        let mut service_costs = groups
+            .iter()
+            .map(|group| {
+                let name = group
+                    .keys
+                    .as_ref()
+                    .and_then(|keys| keys.first())
+                    .cloned()
+                    .unwrap_or_else(|| "Unknown".to_owned());
+                let metric_value = group.metrics.as_ref().and_then(|metrics| metrics.get(UNBLENDED_COST));
+                let amount = metric_value
+                    .and_then(|metric| metric.amount.as_ref())
+                    .map(|amount| amount.to_string())
+                    .unwrap_or_else(|| "0".to_owned());
+                let amount = f32::from_str(amount.as_str()).unwrap_or(0.0);
+                let unit = metric_value
+                    .and_then(|metric| metric.unit.as_ref())
+                    .map(|unit| unit.to_string())
+                    .unwrap_or_else(|| "".to_owned());
+                ServiceCost { name, amount, unit }
+            })
+            .collect::<Vec<ServiceCost>>();
+
+        service_costs.sort_by(|a, b| b.amount.partial_cmp(&a.amount).unwrap_or(std::cmp::Ordering::Equal));
+
+        Ok(service_costs)
+    }
+
+    /// Fetches `trailing_days + 1` days of daily `UnblendedCost`, oldest first, so
+    /// the caller can treat the last entry as "today" and everything before it as
+    /// the trailing baseline for anomaly detection.
+    async fn get_daily_costs(&self, trailing_days: i64) -> Result<Vec<f32>, String> {
+        let response = self.client
+            .get_cost_and_usage(
+                GetCostAndUsageRequest {
+                    granularity: CostGranularityType::Daily.name(),
+                    time_period: get_date_interval_from_end_date(Utc::today(), trailing_days + 1),
+                    metrics: vec!(UNBLENDED_COST.to_owned()),
+                    ..Default::default()
+                })
+            .await
+            .map_err(|_| "Request error")?;
+
+        response
+            .results_by_time
+            .unwrap_or(Vec::new())
+            .iter()
+            .map(|result| {
+                let total = result.total.as_ref().ok_or("Error".to_owned())?;
+                let amount = total
+                    .get(UNBLENDED_COST)
+                    .and_then(|metric| metric.amount.as_ref())
+                    .map(|amount| amount.to_string())
+                    .unwrap_or_else(|| "0".to_owned());
+                f32::from_str(amount.as_str()).map_err(|_| "Parse error Float32".to_owned())
+            })
+            .collect::<Result<Vec<f32>, String>>()
+    }
+}
+
+#[derive(Debug)]
+enum CostError {
+    Request(String),
+    MissingData(String),
+    Parse(String),
+}
+
+impl std::fmt::Display for CostError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CostError::Request(message) => write!(f, "Request error: {}", message),
+            CostError::MissingData(message) => write!(f, "Missing data: {}", message),
+            CostError::Parse(message) => write!(f, "Parse error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for CostError {}
+
+/// A source of cost data. Lets `AwsCostClient` be swapped for other clouds
+/// (GCP Billing, Azure Cost Management) or a deterministic fixture in tests.
+#[async_trait]
+trait CostProvider {
+    async fn get_cost(&self, granularity: CostGranularityType) -> Result<Cost, CostError>;
+}
+
+#[async_trait]
+impl CostProvider for AwsCostClient {
+    async fn get_cost(&self, granularity: CostGranularityType) -> Result<Cost, CostError> {
+        let response = self.client
+            .get_cost_and_usage(
+                GetCostAndUsageRequest {
+                    granularity: granularity.name(),
+                    time_period: get_date_interval_from_end_date(Utc::today(), 1),
+                    metrics: vec!(UNBLENDED_COST.to_owned()),
+                    ..Default::default()
+                })
+            .await
+            .map_err(|_| CostError::Request("Request error".to_owned()))?;
+
+        let results_by_times = response
+            .results_by_time
+            .unwrap_or(Vec::new());
+        let first_result = results_by_times
+            .first()
+            .ok_or(CostError::MissingData("Nothing first result".to_owned()))?;
         let total_cost = first_result
             .total
             .as_ref()
-            .ok_or("Error".to_owned())?;
-        let metric_value = &total_cost[UNBLENDED_COST];
+            .ok_or(CostError::MissingData("Error".to_owned()))?;
+        let metric_value = total_cost.get(UNBLENDED_COST);
         let amount = metric_value
-            .amount
-            .as_ref()
-            .unwrap_or(&"0".to_owned())
-            .to_string();
+            .and_then(|metric| metric.amount.as_ref())
+            .map(|amount| amount.to_string())
+            .unwrap_or_else(|| "0".to_owned());
         let amount = f32::from_str(amount.as_str())
-            .map_err(|_| "Parse error Float32".to_owned())?;
+            .map_err(|_| CostError::Parse("Parse error Float32".to_owned()))?;
         let unit = metric_value
-            .unit
-            .as_ref()
-            .unwrap_or(&"".to_owned())
-            .to_string();
+            .and_then(|metric| metric.unit.as_ref())
+            .map(|unit| unit.to_string())
+            .unwrap_or_else(|| "".to_owned());
 
         Ok(Cost {
             amount,
@@ -118,30 +567,223 @@ impl AwsCostClient {
     }
 }
 
-struct SlackClient<'a> {
+/// A deterministic `CostProvider` for tests. Pairs with `format_report_message`
+/// to unit test the Slack message formatting without calling the live AWS API;
+/// `report_account_cost`'s service-cost breakdown and budget lookup still go
+/// through `AwsCostClient`/`AwsBudgetClient` directly, since those aren't part
+/// of this trait.
+#[cfg(test)]
+struct FixedCostProvider {
+    cost: Cost,
+}
+
+#[cfg(test)]
+impl FixedCostProvider {
+    fn new(amount: f32, unit: &str) -> Self {
+        FixedCostProvider {
+            cost: Cost { amount, unit: unit.to_owned() },
+        }
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl CostProvider for FixedCostProvider {
+    async fn get_cost(&self, _granularity: CostGranularityType) -> Result<Cost, CostError> {
+        Ok(self.cost.clone())
+    }
+}
+
+struct AwsBudgetClient {
+    client: BudgetsClient,
+    account_id: String,
+}
+
+impl AwsBudgetClient {
+    /// Budgets is a single-endpoint API served only out of `us-east-1`,
+    /// regardless of which region the queried account's resources live in, so
+    /// the region is hardcoded rather than taken from account config.
+    fn new(credentials_provider: ProfileProvider, account_id: &str) -> Result<AwsBudgetClient, String> {
+        let http_client = new_us_east_1_http_client()?;
+        Ok(AwsBudgetClient {
+            client: BudgetsClient::new_with(http_client, credentials_provider, Region::UsEast1),
+            account_id: account_id.to_owned(),
+        })
+    }
+
+    async fn get_budget(&self, budget_name: &str) -> Result<Budget, String> {
+        let response = self.client
+            .describe_budget(DescribeBudgetRequest {
+                account_id: self.account_id.clone(),
+                budget_name: budget_name.to_owned(),
+            })
+            .await
+            .map_err(|_| "Request error")?;
+
+        let budget = response.budget.ok_or("Nothing budget".to_owned())?;
+        let budget_limit = budget.budget_limit.ok_or("Nothing budget limit".to_owned())?;
+        let limit = f32::from_str(budget_limit.amount.as_str())
+            .map_err(|_| "Parse error Float32".to_owned())?;
+
+        Ok(Budget {
+            limit,
+            unit: budget_limit.unit,
+        })
+    }
+}
+
+struct SlackClient {
     client: reqwest::Client,
-    token: &'a str,
-    channel_id: &'a str,
+    token: String,
+    channel_id: String,
 }
 
-impl<'a> SlackClient<'a> {
-    fn new(token: &'a str, channel_id: &'a str) -> Result<Self, &'a str> {
-        let client = slack_api::default_client().map_err(|_| "Could not get default_client")?;
+impl SlackClient {
+    fn new(token: &str, channel_id: &str) -> Result<Self, String> {
+        let client = slack_api::default_client()
+            .map_err(|_| "Could not get default_client".to_owned())?;
         Ok(SlackClient {
             client,
-            token,
-            channel_id,
+            token: token.to_owned(),
+            channel_id: channel_id.to_owned(),
         })
     }
 
-    async fn send_message(self, message: &str) -> Result<PostMessageResponse, &str> {
-        slack_api::chat::post_message(&self.client, self.token, &PostMessageRequest {
-            channel: self.channel_id,
+    async fn send_message(&self, message: &str) -> Result<PostMessageResponse, String> {
+        slack_api::chat::post_message(&self.client, self.token.as_str(), &PostMessageRequest {
+            channel: self.channel_id.as_str(),
             text: message,
             ..Default::default()
         }).await.map_err(|error| {
             println!("{:?}", error);
-            "Could not send massage"
+            "Could not send massage".to_owned()
         })
     }
+}
+
+#[async_trait]
+impl Notifier for SlackClient {
+    async fn deliver(&self, when: DateTime<Utc>, message: &str) -> Result<(), String> {
+        let message = format!("[{}] {}", when.format("%Y-%m-%d %H:%M UTC"), message);
+        self.send_message(message.as_str()).await?;
+        Ok(())
+    }
+}
+
+struct WebhookNotifier {
+    client: reqwest::Client,
+    webhook_url: String,
+}
+
+impl WebhookNotifier {
+    fn new(webhook_url: &str) -> Self {
+        WebhookNotifier {
+            client: reqwest::Client::new(),
+            webhook_url: webhook_url.to_owned(),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct WebhookPayload<'a> {
+    content: &'a str,
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    async fn deliver(&self, when: DateTime<Utc>, message: &str) -> Result<(), String> {
+        let content = format!("[{}] {}", when.format("%Y-%m-%d %H:%M UTC"), message);
+        self.client
+            .post(self.webhook_url.as_str())
+            .json(&WebhookPayload { content: content.as_str() })
+            .send()
+            .await
+            .map_err(|_| "Could not send webhook request".to_owned())?
+            .error_for_status()
+            .map_err(|_| "Webhook request returned an error status".to_owned())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn fixed_cost_provider_returns_the_configured_cost() {
+        let provider = FixedCostProvider::new(42.5, "USD");
+        let cost = provider.get_cost(CostGranularityType::Monthly).await.unwrap();
+        assert_eq!(cost.amount, 42.5);
+        assert_eq!(cost.unit, "USD");
+    }
+
+    #[test]
+    fn format_top_services_truncates_to_top_n() {
+        let service_costs = vec!(
+            ServiceCost { name: "EC2".to_owned(), amount: 50.0, unit: "USD".to_owned() },
+            ServiceCost { name: "S3".to_owned(), amount: 10.0, unit: "USD".to_owned() },
+        );
+        let formatted = format_top_services(&service_costs, 1);
+        assert_eq!(formatted, "1. EC2: 50.0 USD");
+    }
+
+    #[tokio::test]
+    async fn format_report_message_reflects_cost_provider_and_anomaly() {
+        let provider = FixedCostProvider::new(950.0, "USD");
+        let cost = provider.get_cost(CostGranularityType::Monthly).await.unwrap();
+        let budget = Budget { limit: 1000.0, unit: "USD".to_owned() };
+        let service_costs = vec!(ServiceCost { name: "EC2".to_owned(), amount: 600.0, unit: "USD".to_owned() });
+        let anomaly = Anomaly {
+            latest_amount: 950.0,
+            mean: 500.0,
+            std_dev: 50.0,
+            percent_change: 0.9,
+            is_anomalous: true,
+        };
+
+        let message = format_report_message("acme", &cost, &budget, &service_costs, Some(&anomaly));
+
+        assert!(message.contains("[acme] Usage cost: 950.0 USD (95% of budget limit 1000.0 USD)"));
+        assert!(message.contains("1. EC2: 600.0 USD"));
+        assert!(message.contains(":warning: Daily spend anomaly detected"));
+    }
+
+    #[tokio::test]
+    async fn format_report_message_omits_warning_when_not_anomalous() {
+        let provider = FixedCostProvider::new(500.0, "USD");
+        let cost = provider.get_cost(CostGranularityType::Monthly).await.unwrap();
+        let budget = Budget { limit: 1000.0, unit: "USD".to_owned() };
+        let service_costs: Vec<ServiceCost> = vec!();
+
+        let message = format_report_message("acme", &cost, &budget, &service_costs, None);
+
+        assert!(!message.contains(":warning:"));
+    }
+
+    #[test]
+    fn detect_anomaly_returns_none_without_enough_history() {
+        assert!(detect_anomaly(&[], 2.0, 0.5).is_none());
+        assert!(detect_anomaly(&[50.0], 2.0, 0.5).is_none());
+    }
+
+    #[test]
+    fn detect_anomaly_flags_a_clear_std_dev_spike() {
+        let daily_amounts = [100.0, 102.0, 98.0, 101.0, 99.0, 110.0];
+        let anomaly = detect_anomaly(&daily_amounts, 2.0, 0.5).unwrap();
+        assert!(anomaly.is_anomalous);
+    }
+
+    #[test]
+    fn detect_anomaly_falls_back_to_percent_change_when_history_has_zero_variance() {
+        let flat_then_spike = [100.0, 100.0, 100.0, 200.0];
+        let anomaly = detect_anomaly(&flat_then_spike, 2.0, 0.5).unwrap();
+        assert_eq!(anomaly.std_dev, 0.0);
+        assert!(anomaly.is_anomalous);
+
+        let flat_then_small_bump = [100.0, 100.0, 100.0, 105.0];
+        let anomaly = detect_anomaly(&flat_then_small_bump, 2.0, 0.5).unwrap();
+        assert_eq!(anomaly.std_dev, 0.0);
+        assert!(!anomaly.is_anomalous);
+    }
+
 }
\ No newline at end of file